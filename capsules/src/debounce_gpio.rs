@@ -0,0 +1,113 @@
+//! Software debounce / glitch-rejection wrapper for `hil::gpio::Interrupt`.
+//!
+//! Mechanical inputs (buttons, rotary encoders, reed switches) bounce for a
+//! few milliseconds after each transition. On a chip with a hardware input
+//! filter, such as the Vorago VA108xx `FilterClkSel`/filter-count
+//! registers, this is rejected in hardware; on chips without one (e.g.
+//! nRF5x), every bounce turns into its own `handle_interrupt`, which can
+//! storm a client with spurious callbacks.
+//!
+//! `DebounceGpio` sits between a `gpio::InterruptPin` and its client. When
+//! the pin fires, instead of immediately delivering the callback, it
+//! disables the pin's interrupt and starts a settle timer on an injected
+//! `hil::time::Alarm`. When the timer expires, it re-enables the pin,
+//! samples its level, and only delivers the callback if the level is still
+//! consistent with the configured edge; for `RisingEdge`/`FallingEdge`, a
+//! transition that reversed itself within the settle window is silently
+//! dropped. For `EitherEdge` every level is consistent, so one callback is
+//! delivered per settle window. Set `pin`/`alarm` as this
+//! wrapper's clients instead of the board's, and call `set_client` with the
+//! real one.
+
+use core::cell::Cell;
+use kernel::common::cells::OptionalCell;
+use kernel::hil::gpio;
+use kernel::hil::time;
+
+/// Settle time (in alarm tics) at which debouncing is disabled and
+/// callbacks are forwarded immediately, as if `DebounceGpio` were not
+/// present. This is the default, so existing clients that never call
+/// `set_settle_time` see no behavior change.
+pub const SETTLE_TIME_DISABLED: u32 = 0;
+
+pub struct DebounceGpio<A: 'static + time::Alarm> {
+    pin: &'static dyn gpio::InterruptPin,
+    alarm: &'static A,
+    client: OptionalCell<&'static dyn gpio::Client>,
+    edge: Cell<gpio::InterruptEdge>,
+    settle_time: Cell<u32>,
+    masked: Cell<bool>,
+}
+
+impl<A: 'static + time::Alarm> DebounceGpio<A> {
+    pub const fn new(pin: &'static dyn gpio::InterruptPin, alarm: &'static A) -> DebounceGpio<A> {
+        DebounceGpio {
+            pin,
+            alarm,
+            client: OptionalCell::empty(),
+            edge: Cell::new(gpio::InterruptEdge::EitherEdge),
+            settle_time: Cell::new(SETTLE_TIME_DISABLED),
+            masked: Cell::new(false),
+        }
+    }
+
+    pub fn set_client(&self, client: &'static dyn gpio::Client) {
+        self.client.set(client);
+    }
+
+    /// Set the settle time, in alarm tics, an edge must persist before its
+    /// callback is delivered. `SETTLE_TIME_DISABLED` (0) turns debouncing
+    /// off again.
+    pub fn set_settle_time(&self, tics: u32) {
+        self.settle_time.set(tics);
+    }
+
+    pub fn enable_interrupts(&self, mode: gpio::InterruptEdge) {
+        self.edge.set(mode);
+        self.masked.set(false);
+        self.pin.enable_interrupts(mode);
+    }
+
+    pub fn disable_interrupts(&self) {
+        self.alarm.disable();
+        self.masked.set(false);
+        self.pin.disable_interrupts();
+    }
+}
+
+impl<A: 'static + time::Alarm> gpio::Client for DebounceGpio<A> {
+    fn fired(&self) {
+        if self.settle_time.get() == SETTLE_TIME_DISABLED {
+            self.client.map(|client| client.fired());
+            return;
+        }
+        if self.masked.replace(true) {
+            // Already settling from an earlier bounce in this burst; the
+            // alarm that is already armed will re-check the final level.
+            return;
+        }
+        // Stop the pin from interrupting again until the settle timer
+        // expires. Otherwise every bounce still wakes the CPU even though
+        // its callback is dropped, which burns exactly the cycles this
+        // wrapper exists to avoid.
+        self.pin.disable_interrupts();
+        let now = self.alarm.now();
+        self.alarm.set_alarm(now.wrapping_add(self.settle_time.get()));
+    }
+}
+
+impl<A: 'static + time::Alarm> time::Client for DebounceGpio<A> {
+    fn fired(&self) {
+        self.masked.set(false);
+        let level = self.pin.read();
+        let consistent = match self.edge.get() {
+            gpio::InterruptEdge::RisingEdge => level,
+            gpio::InterruptEdge::FallingEdge => !level,
+            gpio::InterruptEdge::EitherEdge => true,
+        };
+        self.pin.enable_interrupts(self.edge.get());
+        if consistent {
+            self.client.map(|client| client.fired());
+        }
+    }
+}
@@ -0,0 +1,302 @@
+//! Driver for Hitachi HD44780-compatible character LCDs, wired in 4-bit
+//! mode over `hil::gpio::Output` (RS, E, and D4-D7; R/W is assumed tied
+//! low, i.e. write-only).
+//!
+//! The controller has no way to report when it is done executing an
+//! instruction, so the datasheet instead specifies fixed worst-case
+//! execution times: at least 37µs after most instructions, and 1.52ms
+//! after `clear`/`home`. Because the code here drives pins synchronously
+//! with no built-in delay, those waits are sequenced through an injected
+//! `hil::time::Alarm` state machine rather than busy-waiting, so the rest
+//! of the kernel keeps running while the display catches up. A
+//! `command_complete` callback fires once a command (and its settle time)
+//! has finished.
+//!
+//! This assumes a 2-line display; row addresses are the standard 0x00/0x40
+//! DDRAM bases used by 16x2/20x2 HD44780 modules. Set this struct as the
+//! alarm's client, call `init()` before issuing any other command, and wait
+//! for the `command_complete` callback between commands.
+
+use core::cell::Cell;
+use kernel::common::cells::{OptionalCell, TakeCell};
+use kernel::hil::gpio;
+use kernel::hil::time;
+
+/// Maximum characters buffered by a single `write_string` call.
+pub const BUF_LEN: usize = 40;
+
+/// Number of columns on a standard 16x2 HD44780 module; on a 20x2 module,
+/// columns 16-19 cannot be reached through `set_cursor`. `set_cursor`
+/// rejects any `col` at or beyond this so it can never run past a row's end
+/// into the next row's DDRAM base.
+pub const COLS: usize = 16;
+
+/// DDRAM base address of each display row. Only 2-line displays are
+/// supported; row 0 starts at 0x00, row 1 at 0x40.
+const ROW_OFFSETS: [u8; 2] = [0x00, 0x40];
+
+// Instructions, per the HD44780 datasheet.
+const CMD_CLEAR: u8 = 0x01;
+const CMD_ENTRY_MODE_SET: u8 = 0x06; // increment, no display shift
+const CMD_DISPLAY_ON: u8 = 0x0C; // display on, cursor off, blink off
+const CMD_FUNCTION_SET_4BIT_2LINE: u8 = 0x28;
+const CMD_SET_DDRAM_ADDR: u8 = 0x80;
+
+/// Client for command-completion callbacks.
+pub trait Client {
+    /// Called once the command issued through `HD44780` has finished,
+    /// including any settle time required before the display is ready to
+    /// accept the next command.
+    fn command_complete(&self);
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Settle {
+    /// Waiting >4.1ms after the first forced reset nibble, per the
+    /// HD44780 power-on reset-by-instruction procedure.
+    ResetPulse,
+    /// Waiting >100µs after the second forced reset nibble.
+    ResetSwitch,
+    /// Waiting the >=37µs instruction execution time.
+    Short,
+    /// Waiting the 1.52ms `clear`/`home` execution time.
+    Long,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum State {
+    Idle,
+    /// Sending the 4-bit-mode init sequence; the `u8` is the step already
+    /// issued, whose settle time is running.
+    Init(u8),
+    /// Settling after a single command byte.
+    Settling,
+    /// Sending the characters of a `write_string` call, from this index.
+    WritingString(usize),
+}
+
+pub struct HD44780<A: 'static + time::Alarm> {
+    rs: &'static dyn gpio::Output,
+    en: &'static dyn gpio::Output,
+    data: [&'static dyn gpio::Output; 4], // D4..D7
+    alarm: &'static A,
+    client: OptionalCell<&'static dyn Client>,
+    state: Cell<State>,
+    buffer: TakeCell<'static, [u8]>,
+    buffer_len: Cell<usize>,
+}
+
+impl<A: 'static + time::Alarm> HD44780<A> {
+    pub const fn new(
+        rs: &'static dyn gpio::Output,
+        en: &'static dyn gpio::Output,
+        data: [&'static dyn gpio::Output; 4],
+        alarm: &'static A,
+        buffer: &'static mut [u8],
+    ) -> HD44780<A> {
+        HD44780 {
+            rs,
+            en,
+            data,
+            alarm,
+            client: OptionalCell::empty(),
+            state: Cell::new(State::Idle),
+            buffer: TakeCell::new(buffer),
+            buffer_len: Cell::new(0),
+        }
+    }
+
+    pub fn set_client(&self, client: &'static dyn Client) {
+        self.client.set(client);
+    }
+
+    /// Run the 4-bit-mode init sequence (function set, display on, entry
+    /// mode, clear). Delivers `command_complete` once the display is ready
+    /// to accept ordinary commands.
+    pub fn init(&self) {
+        self.state.set(State::Init(0));
+        self.run_init_step(0);
+    }
+
+    /// Clear the display and return the cursor to the home position.
+    pub fn clear(&self) -> Result<(), ()> {
+        if self.state.get() != State::Idle {
+            return Err(());
+        }
+        self.rs.clear();
+        self.pulse_byte(CMD_CLEAR);
+        self.state.set(State::Settling);
+        self.arm_settle(Settle::Long);
+        Ok(())
+    }
+
+    /// Position the cursor at `row`/`col` (zero-indexed) via
+    /// `SET_DDRAM_ADDR`.
+    pub fn set_cursor(&self, row: usize, col: usize) -> Result<(), ()> {
+        if self.state.get() != State::Idle {
+            return Err(());
+        }
+        if row >= ROW_OFFSETS.len() || col >= COLS {
+            return Err(());
+        }
+        let addr = ROW_OFFSETS[row] + col as u8;
+        self.rs.clear();
+        self.pulse_byte(CMD_SET_DDRAM_ADDR | addr);
+        self.state.set(State::Settling);
+        self.arm_settle(Settle::Short);
+        Ok(())
+    }
+
+    /// Write `s` to the display starting at the current cursor position.
+    /// Characters beyond `BUF_LEN` are dropped.
+    pub fn write_string(&self, s: &str) -> Result<(), ()> {
+        if self.state.get() != State::Idle {
+            return Err(());
+        }
+        let bytes = s.as_bytes();
+        let len = core::cmp::min(bytes.len(), BUF_LEN);
+        if len == 0 {
+            // Nothing to write, but every other command path delivers
+            // `command_complete`; do the same so a client that sequences
+            // its next command off that callback doesn't hang.
+            self.client.map(|client| client.command_complete());
+            return Ok(());
+        }
+        self.buffer.map(|buf| buf[..len].copy_from_slice(&bytes[..len]));
+        self.buffer_len.set(len);
+        self.state.set(State::WritingString(0));
+        self.write_data_byte(bytes[0]);
+        self.arm_settle(Settle::Short);
+        Ok(())
+    }
+
+    fn run_init_step(&self, step: u8) {
+        match step {
+            // Three forced nibbles, required by the datasheet to
+            // resynchronize the controller regardless of whatever mode it
+            // powered up in. The gaps between them are part of the
+            // power-on reset procedure and are much longer than a normal
+            // instruction's execution time.
+            0 => {
+                self.rs.clear();
+                self.write_nibble(0x3);
+                self.arm_settle(Settle::ResetPulse);
+            }
+            1 => {
+                self.rs.clear();
+                self.write_nibble(0x3);
+                self.arm_settle(Settle::ResetSwitch);
+            }
+            2 => {
+                self.rs.clear();
+                self.write_nibble(0x3);
+                self.arm_settle(Settle::Short);
+            }
+            // Switch into 4-bit mode.
+            3 => {
+                self.rs.clear();
+                self.write_nibble(0x2);
+                self.arm_settle(Settle::Short);
+            }
+            4 => {
+                self.rs.clear();
+                self.pulse_byte(CMD_FUNCTION_SET_4BIT_2LINE);
+                self.arm_settle(Settle::Short);
+            }
+            5 => {
+                self.rs.clear();
+                self.pulse_byte(CMD_DISPLAY_ON);
+                self.arm_settle(Settle::Short);
+            }
+            6 => {
+                self.rs.clear();
+                self.pulse_byte(CMD_ENTRY_MODE_SET);
+                self.arm_settle(Settle::Short);
+            }
+            7 => {
+                self.rs.clear();
+                self.pulse_byte(CMD_CLEAR);
+                self.arm_settle(Settle::Long);
+            }
+            _ => {
+                self.state.set(State::Idle);
+                self.client.map(|client| client.command_complete());
+            }
+        }
+    }
+
+    fn write_data_byte(&self, byte: u8) {
+        self.rs.set();
+        self.pulse_byte(byte);
+    }
+
+    /// Write a full byte as a high nibble followed by a low nibble.
+    fn pulse_byte(&self, byte: u8) {
+        self.write_nibble(byte >> 4);
+        self.write_nibble(byte & 0xF);
+    }
+
+    /// Drive D4-D7 with `nibble` and pulse E to latch it.
+    fn write_nibble(&self, nibble: u8) {
+        for (i, pin) in self.data.iter().enumerate() {
+            if nibble & (1 << i) != 0 {
+                pin.set();
+            } else {
+                pin.clear();
+            }
+        }
+        self.en.set();
+        self.en.clear();
+    }
+
+    fn arm_settle(&self, settle: Settle) {
+        let us: u32 = match settle {
+            Settle::ResetPulse => 4_500, // >4.1ms required
+            Settle::ResetSwitch => 150,  // >100us required
+            Settle::Short => 40,         // >=37us required
+            Settle::Long => 1520,
+        };
+        let freq = <A::Frequency as time::Frequency>::frequency();
+        let now = self.alarm.now();
+        self.alarm.set_alarm(now.wrapping_add(Self::ticks_for_micros(freq, us)));
+    }
+
+    /// Convert a microsecond duration into a number of alarm ticks that is
+    /// guaranteed not to undershoot it. Ceil-dividing alone isn't quite
+    /// enough: `now()` may already be partway through its current tick, so
+    /// a literal `ceil(us)` worth of ticks could still elapse in less than
+    /// `us`. Add one extra tick of margin, and never arm for less than two
+    /// ticks total.
+    fn ticks_for_micros(freq: u32, us: u32) -> u32 {
+        let ceil_ticks = ((us as u64 * freq as u64) + 999_999) / 1_000_000;
+        core::cmp::max(ceil_ticks as u32, 1) + 1
+    }
+}
+
+impl<A: 'static + time::Alarm> time::Client for HD44780<A> {
+    fn fired(&self) {
+        match self.state.get() {
+            State::Init(step) => {
+                self.state.set(State::Init(step + 1));
+                self.run_init_step(step + 1);
+            }
+            State::Settling => {
+                self.state.set(State::Idle);
+                self.client.map(|client| client.command_complete());
+            }
+            State::WritingString(i) => {
+                let next = i + 1;
+                if next >= self.buffer_len.get() {
+                    self.state.set(State::Idle);
+                    self.client.map(|client| client.command_complete());
+                } else {
+                    let byte = self.buffer.map(|buf| buf[next]).unwrap_or(0);
+                    self.write_data_byte(byte);
+                    self.state.set(State::WritingString(next));
+                    self.arm_settle(Settle::Short);
+                }
+            }
+            State::Idle => {}
+        }
+    }
+}
@@ -0,0 +1,4 @@
+#![no_std]
+
+pub mod debounce_gpio;
+pub mod hd44780;
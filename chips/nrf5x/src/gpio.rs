@@ -348,6 +348,10 @@ pub struct GPIOPin {
     client: OptionalCell<&'static dyn hil::gpio::Client>,
     gpiote_registers: StaticRef<GpioteRegisters>,
     gpio_registers: StaticRef<GpioRegisters>,
+    /// Set when this pin is using the SENSE/LATCH port-interrupt fallback
+    /// (i.e. no GPIOTE channel was available), so that `is_pending`,
+    /// `disable_interrupts` and `Port::handle_interrupt` know to consult it.
+    sense_mode: OptionalCell<hil::gpio::InterruptEdge>,
 }
 
 impl GPIOPin {
@@ -363,6 +367,7 @@ impl GPIOPin {
                 )
             },
             gpiote_registers: GPIOTE_BASE,
+            sense_mode: OptionalCell::empty(),
         }
     }
 
@@ -371,10 +376,64 @@ impl GPIOPin {
         gpio_regs.pin_cnf[self.pin as usize].write(config);
     }
 
+    /// Like `write_config`, but preserves the other fields of `PIN_CNF`
+    /// instead of overwriting the whole register.
+    pub fn modify_config(&self, config: FieldValue<u32, PinConfig::Register>) {
+        let gpio_regs = &*self.gpio_registers;
+        gpio_regs.pin_cnf[self.pin as usize].modify(config);
+    }
+
     pub fn read_config(&self) -> Option<PinConfig::PULL::Value> {
         let gpio_regs = &*self.gpio_registers;
         gpio_regs.pin_cnf[self.pin as usize].read_as_enum(PinConfig::PULL)
     }
+
+    /// Set the pin's drive configuration to one of the eight `DRIVE` modes
+    /// supported by the hardware. `high_drive_0`/`high_drive_1` request high
+    /// drive (vs. standard drive) for the pin's driven-low/driven-high
+    /// states respectively. Both states are always connected afterwards: an
+    /// earlier `set_open_drain`/`set_open_source` setting is replaced.
+    pub fn set_drive_strength(&self, high_drive_0: bool, high_drive_1: bool) {
+        let drive = match (high_drive_0, high_drive_1) {
+            (false, false) => PinConfig::DRIVE::S0S1,
+            (true, false) => PinConfig::DRIVE::H0S1,
+            (false, true) => PinConfig::DRIVE::S0H1,
+            (true, true) => PinConfig::DRIVE::H0H1,
+        };
+        self.modify_config(drive);
+    }
+
+    /// Disconnect the pin's driven-high state, so that it only ever drives
+    /// low or floats, for sharing a wired-and bus with other open-drain
+    /// drivers. `high_drive` selects high drive (vs. standard drive) for the
+    /// driven-low state.
+    pub fn set_open_drain(&self, high_drive: bool) {
+        let drive = if high_drive {
+            PinConfig::DRIVE::H0D1
+        } else {
+            PinConfig::DRIVE::S0D1
+        };
+        self.modify_config(drive);
+    }
+
+    /// Disconnect the pin's driven-low state, so that it only ever drives
+    /// high or floats, for sharing a wired-or bus with other open-source
+    /// drivers. `high_drive` selects high drive (vs. standard drive) for the
+    /// driven-high state.
+    pub fn set_open_source(&self, high_drive: bool) {
+        let drive = if high_drive {
+            PinConfig::DRIVE::D0H1
+        } else {
+            PinConfig::DRIVE::D0S1
+        };
+        self.modify_config(drive);
+    }
+
+    /// Read back the pin's current `DRIVE` configuration.
+    pub fn drive_mode(&self) -> Option<PinConfig::DRIVE::Value> {
+        let gpio_regs = &*self.gpio_registers;
+        gpio_regs.pin_cnf[self.pin as usize].read_as_enum(PinConfig::DRIVE)
+    }
 }
 
 impl hil::gpio::Configure for GPIOPin {
@@ -472,12 +531,19 @@ impl hil::gpio::Interrupt for GPIOPin {
             let ev = &regs.event_in[channel];
             ev.matches_any(EventsIn::EVENT::Ready)
         } else {
-            false
+            self.sense_mode.is_some() && self.sense_condition_met()
         }
     }
 
     fn enable_interrupts(&self, mode: hil::gpio::InterruptEdge) {
         if let Ok(channel) = self.allocate_channel() {
+            // A GPIOTE channel is available. If this pin was previously
+            // relying on the SENSE/LATCH fallback (e.g. another pin's
+            // channel has since freed up), tear that down first so the pin
+            // doesn't fire through both backends at once.
+            if self.sense_mode.take().is_some() {
+                self.modify_config(PinConfig::SENSE::Disabled);
+            }
             let polarity = match mode {
                 hil::gpio::InterruptEdge::EitherEdge => Config::POLARITY::Toggle,
                 hil::gpio::InterruptEdge::RisingEdge => Config::POLARITY::LoToHi,
@@ -488,7 +554,13 @@ impl hil::gpio::Interrupt for GPIOPin {
             regs.config[channel].write(Config::MODE::Event + Config::PSEL.val(pin) + polarity);
             regs.intenset.set(1 << channel);
         } else {
-            debug!("No available GPIOTE interrupt channels");
+            // All GPIOTE channels are in use. Fall back to the PORT-wide
+            // SENSE/LATCH mechanism instead of losing the interrupt: every
+            // pin's PIN_CNF can independently request a level-sense, which
+            // feeds a single shared PORT event regardless of how many
+            // GPIOTE channels are free.
+            debug!("No available GPIOTE interrupt channels, falling back to SENSE/LATCH");
+            self.enable_sense_interrupt(mode);
         }
     }
 
@@ -499,12 +571,80 @@ impl hil::gpio::Interrupt for GPIOPin {
                 .write(Config::MODE::CLEAR + Config::PSEL::CLEAR + Config::POLARITY::CLEAR);
             regs.intenclr.set(1 << channel);
         }
+        if self.sense_mode.take().is_some() {
+            self.modify_config(PinConfig::SENSE::Disabled);
+        }
     }
 }
 
 impl hil::gpio::InterruptPin for GPIOPin {}
 
+/// The operation a task-mode GPIOTE channel performs on its pin when its
+/// `TASKS_OUT` is triggered, i.e. `Config::POLARITY` as interpreted in task mode.
+#[derive(Copy, Clone, PartialEq)]
+pub enum TaskAction {
+    Set,
+    Clear,
+    Toggle,
+}
+
+/// The register addresses of a GPIOTE channel's `TASKS_OUT\[n\]` and
+/// `EVENTS_IN\[n\]`, suitable for programming into a PPI/DPPI channel so that a
+/// peripheral can trigger a pin operation, or a pin edge can trigger a
+/// peripheral task, without CPU involvement.
+#[derive(Copy, Clone)]
+pub struct GpioteEndpoint {
+    /// Address of `TASKS_OUT\[n\]`. Triggering this task performs the channel's
+    /// configured `TaskAction` on its pin.
+    pub task_out: u32,
+    /// Address of `EVENTS_IN\[n\]`. This event is set when the channel's
+    /// configured edge occurs on its pin.
+    pub event_in: u32,
+}
+
 impl GPIOPin {
+    /// Bind this pin to a GPIOTE channel in task mode, so that triggering the
+    /// channel's `TASKS_OUT` performs `action` on the pin. `initial_high` sets
+    /// the pin's level via `OUTINIT` before the task is first triggered.
+    /// Returns the allocated channel index, or `Err` if all channels are
+    /// already in use.
+    pub fn enable_task(&self, action: TaskAction, initial_high: bool) -> Result<usize, ()> {
+        let channel = self.allocate_channel()?;
+        let regs = &*self.gpiote_registers;
+        let pin: u32 = (GPIO_PER_PORT as u32 * self.port as u32) + self.pin as u32;
+        let polarity = match action {
+            TaskAction::Set => Config::POLARITY::LoToHi,
+            TaskAction::Clear => Config::POLARITY::HiToLo,
+            TaskAction::Toggle => Config::POLARITY::Toggle,
+        };
+        let outinit = if initial_high {
+            Config::OUTINIT::High
+        } else {
+            Config::OUTINIT::Low
+        };
+        regs.config[channel]
+            .write(Config::MODE::Task + Config::PSEL.val(pin) + polarity + outinit);
+        Ok(channel)
+    }
+
+    /// Release a GPIOTE channel previously bound with `enable_task`.
+    pub fn disable_task(&self, channel: usize) {
+        let regs = &*self.gpiote_registers;
+        regs.config[channel]
+            .write(Config::MODE::CLEAR + Config::PSEL::CLEAR + Config::POLARITY::CLEAR);
+    }
+
+    /// Return the PPI/DPPI endpoint addresses for a GPIOTE channel, for wiring
+    /// into a peripheral event (to drive `TASKS_OUT`) or a peripheral task (to
+    /// be driven by `EVENTS_IN`).
+    pub fn channel_endpoint(&self, channel: usize) -> GpioteEndpoint {
+        let regs = &*self.gpiote_registers;
+        GpioteEndpoint {
+            task_out: &regs.task_out[channel] as *const _ as u32,
+            event_in: &regs.event_in[channel] as *const _ as u32,
+        }
+    }
+
     /// Allocate a GPIOTE channel
     /// If the channel couldn't be allocated return error instead
     fn allocate_channel(&self) -> Result<usize, ()> {
@@ -535,6 +675,118 @@ impl GPIOPin {
             client.fired();
         });
     }
+
+    /// Program `PIN_CNF\[pin\].SENSE` to initially arm for the given edge.
+    /// `RisingEdge` and `FallingEdge` sense high and low respectively;
+    /// `EitherEdge` senses the opposite of the pin's current level, so that
+    /// the next transition (in either direction) triggers the PORT event.
+    /// Used only for the first arm; after a fire, `toggle_sense_direction`
+    /// re-arms it instead (see its doc comment for why).
+    fn set_sense_for_edge(&self, mode: hil::gpio::InterruptEdge) {
+        let gpio_regs = &*self.gpio_registers;
+        let level = gpio_regs.in_.get() & (1 << self.pin) != 0;
+        let sense = match mode {
+            hil::gpio::InterruptEdge::RisingEdge => PinConfig::SENSE::High,
+            hil::gpio::InterruptEdge::FallingEdge => PinConfig::SENSE::Low,
+            hil::gpio::InterruptEdge::EitherEdge => {
+                if level {
+                    PinConfig::SENSE::Low
+                } else {
+                    PinConfig::SENSE::High
+                }
+            }
+        };
+        self.modify_config(sense);
+    }
+
+    /// Whether this pin's SENSE is currently configured to fire on a high
+    /// level, i.e. whether the transition that just fired it was a rising
+    /// edge (as opposed to a falling one).
+    fn sense_was_high(&self) -> bool {
+        let gpio_regs = &*self.gpio_registers;
+        matches!(
+            gpio_regs.pin_cnf[self.pin as usize].read_as_enum(PinConfig::SENSE),
+            Some(PinConfig::SENSE::Value::High)
+        )
+    }
+
+    /// Flip SENSE to await the complementary level. Re-arming the *same*
+    /// level immediately after it fires would re-latch for as long as the
+    /// pin stays there (e.g. a held-down button), turning one edge into an
+    /// interrupt storm. Toggling instead makes the next PORT event for this
+    /// pin wait for it to actually return to the other level; the caller
+    /// filters on the direction of the transition that just happened to
+    /// decide whether `RisingEdge`/`FallingEdge` clients should be told
+    /// about it.
+    fn toggle_sense_direction(&self) {
+        let sense = if self.sense_was_high() {
+            PinConfig::SENSE::Low
+        } else {
+            PinConfig::SENSE::High
+        };
+        self.modify_config(sense);
+    }
+
+    /// Whether this pin's configured SENSE level currently matches its input
+    /// level, i.e. whether it is the reason the shared PORT event fired.
+    fn sense_condition_met(&self) -> bool {
+        let gpio_regs = &*self.gpio_registers;
+        let level = gpio_regs.in_.get() & (1 << self.pin) != 0;
+        match gpio_regs.pin_cnf[self.pin as usize].read_as_enum(PinConfig::SENSE) {
+            Some(PinConfig::SENSE::Value::High) => level,
+            Some(PinConfig::SENSE::Value::Low) => !level,
+            _ => false,
+        }
+    }
+
+    /// Enable the SENSE/LATCH port-interrupt fallback for `mode`, used once
+    /// all GPIOTE channels are already allocated.
+    fn enable_sense_interrupt(&self, mode: hil::gpio::InterruptEdge) {
+        self.sense_mode.set(mode);
+        self.set_sense_for_edge(mode);
+
+        let gpiote_regs = &*self.gpiote_registers;
+        gpiote_regs.intenset.write(Intenset::PORT::SET);
+
+        self.enable_latch_detect();
+    }
+
+    /// On nRF52, switch DETECT into LDETECT mode so that LATCH accumulates
+    /// edges that arrive close together instead of being overwritten by the
+    /// default "most recent pin" DETECT behavior. nRF51 has no LATCH/DETECT
+    /// registers, so this is a no-op there.
+    #[cfg(feature = "nrf52")]
+    fn enable_latch_detect(&self) {
+        let gpio_regs = &*self.gpio_registers;
+        gpio_regs.detect_mode.write(DetectMode::DETECTMODE::LDDETECT);
+    }
+
+    #[cfg(feature = "nrf51")]
+    fn enable_latch_detect(&self) {}
+
+    /// Whether this pin's bit is latched in the nRF52 LATCH register.
+    #[cfg(feature = "nrf52")]
+    fn sense_fired(&self) -> bool {
+        let gpio_regs = &*self.gpio_registers;
+        gpio_regs.latch.get() & (1 << self.pin) != 0
+    }
+
+    /// nRF51 has no LATCH register, so we fall back to the same level check
+    /// used by `is_pending`.
+    #[cfg(feature = "nrf51")]
+    fn sense_fired(&self) -> bool {
+        self.sense_condition_met()
+    }
+
+    /// Clear this pin's latched bit. Writing a '1' to a LATCH bit clears it.
+    #[cfg(feature = "nrf52")]
+    fn clear_sense_latch(&self) {
+        let gpio_regs = &*self.gpio_registers;
+        gpio_regs.latch.set(1 << self.pin);
+    }
+
+    #[cfg(feature = "nrf51")]
+    fn clear_sense_latch(&self) {}
 }
 
 pub struct Port {
@@ -571,5 +823,35 @@ impl Port {
                 self.pins[pin].handle_interrupt();
             }
         }
+
+        // PORT is the shared SENSE/LATCH fallback event used once all GPIOTE
+        // channels are allocated (see `enable_sense_interrupt`). It does not
+        // identify which pin(s) fired, so every pin using the fallback must
+        // be checked.
+        if regs.event_port.matches_any(EventsPort::PINS::Ready) {
+            regs.event_port.write(EventsPort::PINS::NotReady);
+            for pin in self.pins.iter() {
+                pin.sense_mode.map(|mode| {
+                    if pin.sense_fired() {
+                        pin.clear_sense_latch();
+                        // Determine which way the pin just transitioned
+                        // before toggling SENSE to await the opposite
+                        // level (see `toggle_sense_direction`): a pin
+                        // sensed high fired because it went high, i.e. a
+                        // rising edge.
+                        let rising = pin.sense_was_high();
+                        pin.toggle_sense_direction();
+                        let deliver = match *mode {
+                            hil::gpio::InterruptEdge::EitherEdge => true,
+                            hil::gpio::InterruptEdge::RisingEdge => rising,
+                            hil::gpio::InterruptEdge::FallingEdge => !rising,
+                        };
+                        if deliver {
+                            pin.handle_interrupt();
+                        }
+                    }
+                });
+            }
+        }
     }
 }